@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Error, GitProject, IssueRecorder, MergeRequest, Result};
+
+/// A project hosted on GitHub, identified by `owner/repo`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubProject {
+    owner: String,
+    repo: String,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+impl GithubProject {
+    fn api_base(&self) -> String {
+        format!("https://api.github.com/repos/{}/{}", self.owner, self.repo)
+    }
+
+    /// Perform a GET request against the GitHub API and parse the result as JSON.
+    fn get(&self, url: &str) -> Result<Value> {
+        let mut req = ureq::get(url)
+            .set("Accept", "application/vnd.github+json")
+            .set("User-Agent", "doublegit");
+        if let Some(token) = &self.token {
+            req = req.set("Authorization", &format!("Bearer {}", token));
+        }
+        req.call()
+            .map_err(|e| Error::Http(e.to_string()))?
+            .into_json()
+            .map_err(Error::Io)
+    }
+
+    /// Record every comment on an issue/PR, following `comments_url`'s
+    /// pagination (GitHub defaults to 30 comments per page) instead of
+    /// trusting a single page, same as the issues loop in `get_issues`.
+    fn record_comments(
+        &self,
+        recorder: &mut IssueRecorder,
+        issue_id: &str,
+        comments_url: &str,
+    ) -> Result<()> {
+        let mut page = 1u32;
+        loop {
+            let url = format!("{}?per_page=100&page={}", comments_url, page);
+            let comments = self.get(&url)?;
+            let comments = comments.as_array().ok_or_else(|| {
+                Error::Http("Expected an array of comments".into())
+            })?;
+            if comments.is_empty() {
+                break;
+            }
+
+            for comment in comments {
+                let comment_id = comment["id"].to_string();
+                let text = comment["body"].as_str();
+                recorder.record_comment(issue_id, Some(&comment_id), None, text)?;
+            }
+
+            if comments.len() < 100 {
+                break;
+            }
+            page += 1;
+        }
+        Ok(())
+    }
+}
+
+impl GitProject for GithubProject {
+    fn git_url(&self) -> Option<String> {
+        Some(format!("https://github.com/{}/{}.git", self.owner, self.repo))
+    }
+
+    fn get_issues(
+        &self,
+        mut recorder: IssueRecorder,
+        last: Option<String>,
+    ) -> Result<Option<String>> {
+        let mut newest = last.clone();
+        let mut page = 1u32;
+
+        loop {
+            let mut url = format!(
+                "{}/issues?state=all&sort=updated&direction=asc&per_page=100&page={}",
+                self.api_base(),
+                page,
+            );
+            if let Some(since) = &last {
+                url.push_str(&format!("&since={}", since));
+            }
+
+            let issues = self.get(&url)?;
+            let issues = issues.as_array().ok_or_else(|| {
+                Error::Http("Expected an array of issues".into())
+            })?;
+            if issues.is_empty() {
+                break;
+            }
+
+            for issue in issues {
+                let id = issue["number"].to_string();
+                let title = issue["title"].as_str().unwrap_or_default();
+                let description = issue["body"].as_str();
+                let state = issue["state"].as_str().unwrap_or("open");
+
+                let merge_request = if issue.get("pull_request").is_some() {
+                    let pr = self.get(&format!("{}/pulls/{}", self.api_base(), id))?;
+                    Some(MergeRequest {
+                        base: pr["base"]["ref"].as_str().unwrap_or_default().into(),
+                        head: pr["head"]["ref"].as_str().unwrap_or_default().into(),
+                    })
+                } else {
+                    None
+                };
+
+                recorder.record_issue(&id, title, description, state, merge_request)?;
+
+                if let Some(comments_url) = issue["comments_url"].as_str() {
+                    self.record_comments(&mut recorder, &id, comments_url)?;
+                }
+
+                if let Some(updated_at) = issue["updated_at"].as_str() {
+                    newest = Some(updated_at.into());
+                }
+            }
+
+            if issues.len() < 100 {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(newest)
+    }
+}
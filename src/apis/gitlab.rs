@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Error, GitProject, IssueRecorder, MergeRequest, Result};
+
+fn default_base_url() -> String {
+    "https://gitlab.com".into()
+}
+
+/// A project hosted on GitLab.com or a self-hosted GitLab instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitlabProject {
+    #[serde(default = "default_base_url")]
+    base_url: String,
+    /// Either `namespace/project` or the numeric project id
+    project: String,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+impl GitlabProject {
+    fn api_base(&self) -> String {
+        format!(
+            "{}/api/v4/projects/{}",
+            self.base_url,
+            self.project.replace('/', "%2F"),
+        )
+    }
+
+    /// Perform a GET request against the GitLab API and parse the result as JSON.
+    fn get(&self, url: &str) -> Result<Value> {
+        let mut req = ureq::get(url);
+        if let Some(token) = &self.token {
+            req = req.set("PRIVATE-TOKEN", token);
+        }
+        req.call()
+            .map_err(|e| Error::Http(e.to_string()))?
+            .into_json()
+            .map_err(Error::Io)
+    }
+
+    /// Fetch every page of `path` (`issues` or `merge_requests`), optionally
+    /// restricted to entries updated after the given cursor.
+    fn fetch_all(&self, path: &str, updated_after: &Option<String>) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let mut url = format!(
+                "{}/{}?scope=all&order_by=updated_at&sort=asc&per_page=100&page={}",
+                self.api_base(),
+                path,
+                page,
+            );
+            if let Some(since) = updated_after {
+                url.push_str(&format!("&updated_after={}", since));
+            }
+
+            let batch = self.get(&url)?;
+            let batch = batch.as_array().ok_or_else(|| {
+                Error::Http(format!("Expected an array of {}", path))
+            })?;
+            if batch.is_empty() {
+                break;
+            }
+
+            let len = batch.len();
+            items.extend(batch.iter().cloned());
+            if len < 100 {
+                break;
+            }
+            page += 1;
+        }
+        Ok(items)
+    }
+
+    /// Record every note on an issue/MR, paginating `notes_url` (GitLab
+    /// defaults to 20 notes per page) the same way `fetch_all` paginates
+    /// issues and merge requests, instead of trusting a single page.
+    fn record_notes(
+        &self,
+        recorder: &mut IssueRecorder,
+        issue_id: &str,
+        notes_url: &str,
+    ) -> Result<()> {
+        let mut page = 1u32;
+        loop {
+            let url = format!("{}?per_page=100&page={}", notes_url, page);
+            let notes = self.get(&url)?;
+            let notes = notes.as_array().ok_or_else(|| {
+                Error::Http("Expected an array of notes".into())
+            })?;
+            if notes.is_empty() {
+                break;
+            }
+
+            for note in notes {
+                let note_id = note["id"].to_string();
+                let text = note["body"].as_str();
+                recorder.record_comment(issue_id, Some(&note_id), None, text)?;
+            }
+
+            if notes.len() < 100 {
+                break;
+            }
+            page += 1;
+        }
+        Ok(())
+    }
+}
+
+impl GitProject for GitlabProject {
+    fn git_url(&self) -> Option<String> {
+        Some(format!("{}/{}.git", self.base_url, self.project))
+    }
+
+    fn get_issues(
+        &self,
+        mut recorder: IssueRecorder,
+        last: Option<String>,
+    ) -> Result<Option<String>> {
+        let mut newest = last.clone();
+
+        for issue in self.fetch_all("issues", &last)? {
+            let id = format!("issue-{}", issue["iid"]);
+            let title = issue["title"].as_str().unwrap_or_default();
+            let description = issue["description"].as_str();
+            let state = issue["state"].as_str().unwrap_or("opened");
+
+            recorder.record_issue(&id, title, description, state, None)?;
+            if let Some(notes_url) = issue["_links"]["notes"].as_str() {
+                self.record_notes(&mut recorder, &id, notes_url)?;
+            }
+
+            if let Some(updated_at) = issue["updated_at"].as_str() {
+                newest = Some(newer(newest.as_deref(), updated_at));
+            }
+        }
+
+        for mr in self.fetch_all("merge_requests", &last)? {
+            let id = format!("mr-{}", mr["iid"]);
+            let title = mr["title"].as_str().unwrap_or_default();
+            let description = mr["description"].as_str();
+            let state = mr["state"].as_str().unwrap_or("opened");
+            let merge_request = Some(MergeRequest {
+                base: mr["target_branch"].as_str().unwrap_or_default().into(),
+                head: mr["source_branch"].as_str().unwrap_or_default().into(),
+            });
+
+            recorder.record_issue(&id, title, description, state, merge_request)?;
+            if let Some(notes_url) = mr["_links"]["notes"].as_str() {
+                self.record_notes(&mut recorder, &id, notes_url)?;
+            }
+
+            if let Some(updated_at) = mr["updated_at"].as_str() {
+                newest = Some(newer(newest.as_deref(), updated_at));
+            }
+        }
+
+        Ok(newest)
+    }
+}
+
+/// Return whichever of `current`/`candidate` sorts later; GitLab's
+/// `updated_at` timestamps are ISO-8601 and compare correctly as strings.
+fn newer(current: Option<&str>, candidate: &str) -> String {
+    match current {
+        Some(c) if c >= candidate => c.to_string(),
+        _ => candidate.to_string(),
+    }
+}
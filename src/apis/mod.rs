@@ -1,16 +1,34 @@
 use erased_serde::Serialize;
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, OptionalExtension};
 use serde_json::Value;
 use std::fs::File;
 use std::path::Path;
 
 mod github;
-//mod register;
+mod gitlab;
+mod register;
 
 pub enum Error {
     Io(std::io::Error),
+    Sql(rusqlite::Error),
+    Http(String),
+    Config(String),
     NotSupported,
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sql(e)
+    }
+}
+
 type Result<T> = std::result::Result<T, Error>;
 
 /// A Git platform, from which we can get projects.
@@ -37,12 +55,18 @@ trait GitProject: Serialize {
     /// Get the Git URL for this project, if supported
     fn git_url(&self) -> Option<String>;
 
-    /// Read the issues/merge requests from this project, if supported
-    fn get_issues(
+    /// Read the issues/merge requests from this project, if supported.
+    ///
+    /// `last` is the cursor returned by the previous call (e.g. an
+    /// `updated_at` watermark or page token), or `None` on the first run.
+    /// Returns the cursor to pass in on the next call.
+    fn get_issues<'a>(
         &self,
-        recorder: IssueRecorder,
+        recorder: IssueRecorder<'a>,
         last: Option<String>,
-    ) -> Result<()>;
+    ) -> Result<Option<String>> {
+        Err(Error::NotSupported)
+    }
 }
 
 /// Represent merge request information, that may be attached to issues
@@ -53,23 +77,87 @@ pub struct MergeRequest {
     pub head: String,
 }
 
-/// Recorder object through which `GitProject::get_issues()` can record issues
-pub struct IssueRecorder {
+/// Recorder object through which `GitProject::get_issues()` can record issues.
+///
+/// Mirrors the temporal pattern used for the `refs` table: every observed
+/// state of an issue or comment gets its own row with `from_date` set, and
+/// the previous row is closed by setting its `to_date` once the content
+/// actually changes.
+pub struct IssueRecorder<'a> {
+    tx: &'a rusqlite::Transaction<'a>,
+    date: &'a str,
 }
 
-impl IssueRecorder {
-    /// Record a new issue
+impl<'a> IssueRecorder<'a> {
+    fn new(tx: &'a rusqlite::Transaction<'a>, date: &'a str) -> Self {
+        IssueRecorder { tx, date }
+    }
+
+    /// Record a new issue, or a new observed state of an existing issue.
+    ///
+    /// A new history row is only written if the title, body, or state
+    /// differ from the latest recorded row for this issue.
     pub fn record_issue(
         &mut self,
         id: &str,
         title: &str,
         description: Option<&str>,
+        state: &str,
         merge_request: Option<MergeRequest>,
     ) -> Result<()> {
-        unimplemented!()
+        let current: Option<(String, Option<String>, String)> = self.tx.query_row(
+            "
+            SELECT title, body, state FROM issues
+            WHERE id=?
+            ORDER BY from_date DESC
+            LIMIT 1;
+            ",
+            &[id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).optional()?;
+
+        let changed = match &current {
+            Some((t, b, s)) => t != title || b.as_deref() != description || s != state,
+            None => true,
+        };
+
+        if changed {
+            if current.is_some() {
+                self.tx.execute(
+                    "UPDATE issues SET to_date=? WHERE id=? AND to_date IS NULL;",
+                    &[self.date, id],
+                )?;
+            }
+            self.tx.execute(
+                "
+                INSERT INTO issues(id, from_date, to_date, title, body, state)
+                VALUES(?, ?, NULL, ?, ?, ?);
+                ",
+                &[
+                    &id as &dyn ToSql,
+                    &self.date,
+                    &title,
+                    &description,
+                    &state,
+                ],
+            )?;
+        }
+
+        if let Some(mr) = merge_request {
+            self.tx.execute(
+                "
+                INSERT OR REPLACE INTO merge_requests(issue_id, base, head)
+                VALUES(?, ?, ?);
+                ",
+                &[id, mr.base.as_str(), mr.head.as_str()],
+            )?;
+        }
+
+        Ok(())
     }
 
-    /// Record a comment in an issue's thread
+    /// Record a comment in an issue's thread, or a new observed state of an
+    /// existing comment (e.g. after it was edited).
     pub fn record_comment(
         &mut self,
         issue_id: &str,
@@ -77,7 +165,51 @@ impl IssueRecorder {
         parent: Option<&str>,
         text: Option<&str>,
     ) -> Result<()> {
-        unimplemented!()
+        let current: Option<Option<String>> = match id {
+            Some(id) => self.tx.query_row(
+                "
+                SELECT text FROM comments
+                WHERE issue_id=? AND id=?
+                ORDER BY from_date DESC
+                LIMIT 1;
+                ",
+                &[issue_id, id],
+                |row| row.get(0),
+            ).optional()?,
+            None => None,
+        };
+
+        let changed = match &current {
+            Some(t) => t.as_deref() != text,
+            None => true,
+        };
+
+        if changed {
+            if id.is_some() && current.is_some() {
+                self.tx.execute(
+                    "
+                    UPDATE comments SET to_date=?
+                    WHERE issue_id=? AND id=? AND to_date IS NULL;
+                    ",
+                    &[Some(self.date), Some(issue_id), id],
+                )?;
+            }
+            self.tx.execute(
+                "
+                INSERT INTO comments(issue_id, id, parent, from_date, to_date, text)
+                VALUES(?, ?, ?, ?, NULL, ?);
+                ",
+                &[
+                    &issue_id as &dyn ToSql,
+                    &id,
+                    &parent,
+                    &self.date,
+                    &text,
+                ],
+            )?;
+        }
+
+        Ok(())
     }
 }
 
@@ -122,21 +254,266 @@ pub fn update_with_date(
         return Err(crate::Error::Config("Config is not an object".into()));
     };
 
-    // TODO: Look up API
-    assert!(type_name == "github");
+    // Load configuration object via the platform registry
+    let project = register::load_project(&type_name, config)
+        .map_err(|e| crate::Error::Config(
+            format!("Unsupported or invalid {} config: {}", type_name, e)
+        ))?;
+
+    // Open database, alongside the refs archived by the `git` module
+    let db_path = path.join("gitarchive.sqlite3");
+    let mut db = Connection::open(db_path)?;
+    db.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS issues(
+            id TEXT NOT NULL,
+            from_date DATETIME NOT NULL,
+            to_date DATETIME NULL,
+            title TEXT NOT NULL,
+            body TEXT NULL,
+            state TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS comments(
+            issue_id TEXT NOT NULL,
+            id TEXT NULL,
+            parent TEXT NULL,
+            from_date DATETIME NOT NULL,
+            to_date DATETIME NULL,
+            text TEXT NULL
+        );
+        CREATE TABLE IF NOT EXISTS merge_requests(
+            issue_id TEXT NOT NULL PRIMARY KEY,
+            base TEXT NOT NULL,
+            head TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sync_state(
+            key TEXT NOT NULL PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        ",
+    )?;
+    let tx = db.transaction()?;
 
-    // Load configuration object
-    let project: github::GithubProject =
-        serde_json::from_value(config)
+    // Convert time to string, matching the `refs` temporal format
+    let date = date.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    // Resume from the cursor left by the previous run, if any
+    let last: Option<String> = tx.query_row(
+        "SELECT value FROM sync_state WHERE key='issues_last';",
+        rusqlite::NO_PARAMS,
+        |row| row.get(0),
+    ).optional()?;
+
+    let recorder = IssueRecorder::new(&tx, &date);
+    let new_last = project.get_issues(recorder, last)
         .map_err(|e| crate::Error::Config(
-            format!("Invalid {} config: {}", type_name, e)
+            format!("Error archiving issues: {}", e)
         ))?;
 
-    // TODO: Update it
+    if let Some(new_last) = new_last {
+        tx.execute(
+            "
+            INSERT INTO sync_state(key, value) VALUES('issues_last', ?)
+            ON CONFLICT(key) DO UPDATE SET value=excluded.value;
+            ",
+            &[&new_last],
+        )?;
+    }
+
+    tx.commit()?;
 
     Ok(())
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Sql(e) => write!(f, "database error: {}", e),
+            Error::Http(e) => write!(f, "HTTP error: {}", e),
+            Error::Config(e) => write!(f, "configuration error: {}", e),
+            Error::NotSupported => write!(f, "operation not supported by this platform"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IssueRecorder;
+
+    /// An in-memory database with just the `issues`/`comments`/`merge_requests`
+    /// tables, matching the schema `update_with_date` creates in
+    /// `gitarchive.sqlite3`.
+    fn test_db() -> rusqlite::Connection {
+        let db = rusqlite::Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            "
+            CREATE TABLE issues(
+                id TEXT NOT NULL,
+                from_date DATETIME NOT NULL,
+                to_date DATETIME NULL,
+                title TEXT NOT NULL,
+                body TEXT NULL,
+                state TEXT NOT NULL
+            );
+            CREATE TABLE comments(
+                issue_id TEXT NOT NULL,
+                id TEXT NULL,
+                parent TEXT NULL,
+                from_date DATETIME NOT NULL,
+                to_date DATETIME NULL,
+                text TEXT NULL
+            );
+            CREATE TABLE merge_requests(
+                issue_id TEXT NOT NULL PRIMARY KEY,
+                base TEXT NOT NULL,
+                head TEXT NOT NULL
+            );
+            ",
+        ).unwrap();
+        db
+    }
+
+    fn issue_rows(tx: &rusqlite::Transaction, id: &str) -> Vec<(String, Option<String>, Option<String>)> {
+        let mut stmt = tx.prepare(
+            "SELECT from_date, to_date, title FROM issues WHERE id=? ORDER BY from_date ASC;",
+        ).unwrap();
+        stmt.query_map(&[id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_record_issue_no_change_no_new_row() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_issue("1", "Title", Some("Body"), "open", None).unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-02 00:00:00");
+        recorder.record_issue("1", "Title", Some("Body"), "open", None).unwrap();
+
+        let rows = issue_rows(&tx, "1");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, "2026-01-01 00:00:00");
+        assert_eq!(rows[0].1, None);
+    }
+
+    #[test]
+    fn test_record_issue_change_closes_and_opens() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_issue("1", "Title", Some("Body"), "open", None).unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-02 00:00:00");
+        recorder.record_issue("1", "New Title", Some("Body"), "open", None).unwrap();
+
+        let rows = issue_rows(&tx, "1");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, "2026-01-01 00:00:00");
+        assert_eq!(rows[0].1, Some("2026-01-02 00:00:00".to_string()));
+        assert_eq!(rows[0].2, "Title");
+        assert_eq!(rows[1].0, "2026-01-02 00:00:00");
+        assert_eq!(rows[1].1, None);
+        assert_eq!(rows[1].2, "New Title");
+    }
+
+    #[test]
+    fn test_record_issue_state_change_also_opens_new_row() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_issue("1", "Title", Some("Body"), "open", None).unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-02 00:00:00");
+        recorder.record_issue("1", "Title", Some("Body"), "closed", None).unwrap();
+
+        let rows = issue_rows(&tx, "1");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1, Some("2026-01-02 00:00:00".to_string()));
+    }
+
+    #[test]
+    fn test_record_comment_new() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_comment("1", Some("c1"), None, Some("Hello")).unwrap();
+
+        let mut stmt = tx.prepare(
+            "SELECT from_date, to_date, text FROM comments WHERE issue_id='1' AND id='c1';",
+        ).unwrap();
+        let rows: Vec<(String, Option<String>, Option<String>)> = stmt
+            .query_map(rusqlite::NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1, None);
+        assert_eq!(rows[0].2, Some("Hello".to_string()));
+    }
+
+    #[test]
+    fn test_record_comment_edit_closes_and_opens() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_comment("1", Some("c1"), None, Some("Hello")).unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-02 00:00:00");
+        recorder.record_comment("1", Some("c1"), None, Some("Hello, edited")).unwrap();
+
+        let mut stmt = tx.prepare(
+            "SELECT from_date, to_date, text FROM comments WHERE issue_id='1' AND id='c1' ORDER BY from_date ASC;",
+        ).unwrap();
+        let rows: Vec<(String, Option<String>, Option<String>)> = stmt
+            .query_map(rusqlite::NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1, Some("2026-01-02 00:00:00".to_string()));
+        assert_eq!(rows[0].2, Some("Hello".to_string()));
+        assert_eq!(rows[1].1, None);
+        assert_eq!(rows[1].2, Some("Hello, edited".to_string()));
+    }
+
+    #[test]
+    fn test_record_comment_unidentified_always_inserts() {
+        let mut db = test_db();
+        let tx = db.transaction().unwrap();
+
+        // Comments without a stable id (id=None) can't be matched back up,
+        // so every call records a fresh row rather than ever closing one.
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-01 00:00:00");
+        recorder.record_comment("1", None, None, Some("Hello")).unwrap();
+
+        let mut recorder = IssueRecorder::new(&tx, "2026-01-02 00:00:00");
+        recorder.record_comment("1", None, None, Some("Hello")).unwrap();
+
+        let mut stmt = tx.prepare(
+            "SELECT to_date FROM comments WHERE issue_id='1';",
+        ).unwrap();
+        let rows: Vec<Option<String>> = stmt
+            .query_map(rusqlite::NO_PARAMS, |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|to_date| to_date.is_none()));
+    }
+}
+
 /*
 /// Config file, either for a project or a collection of projects.
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use serde_json::Value;
+
+use super::{Error, GitProject, Result};
+
+/// Builds a boxed `GitProject` from its (type-stripped) `doublegit.json` body.
+type Constructor = fn(Value) -> Result<Box<dyn GitProject>>;
+
+lazy_static! {
+    static ref REGISTRY: HashMap<&'static str, Constructor> = {
+        let mut m: HashMap<&'static str, Constructor> = HashMap::new();
+        m.insert("github", github_project as Constructor);
+        m.insert("gitlab", gitlab_project as Constructor);
+        m
+    };
+}
+
+fn github_project(config: Value) -> Result<Box<dyn GitProject>> {
+    let project: super::github::GithubProject = serde_json::from_value(config)
+        .map_err(|e| Error::Config(format!("Invalid github config: {}", e)))?;
+    Ok(Box::new(project))
+}
+
+fn gitlab_project(config: Value) -> Result<Box<dyn GitProject>> {
+    let project: super::gitlab::GitlabProject = serde_json::from_value(config)
+        .map_err(|e| Error::Config(format!("Invalid gitlab config: {}", e)))?;
+    Ok(Box::new(project))
+}
+
+/// Build a `GitProject` for the given `doublegit.json` `type`, dispatching to
+/// the constructor registered under that name in `REGISTRY`. Adding a new
+/// platform is just adding an entry there.
+pub fn load_project(type_name: &str, config: Value) -> Result<Box<dyn GitProject>> {
+    match REGISTRY.get(type_name) {
+        Some(ctor) => ctor(config),
+        None => Err(Error::NotSupported),
+    }
+}
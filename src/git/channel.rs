@@ -0,0 +1,145 @@
+use regex::Regex;
+use std::collections::BTreeSet;
+
+use crate::Error;
+
+/// A single `regex:replacement1 replacement2 ...` routing rule.
+struct Rule {
+    regex: Regex,
+    replacements: Vec<String>,
+}
+
+/// Channel-routing rules, in the same comma-separated `regex:replacement`
+/// scheme used by label-tracker: a ref is archived only if some rule's
+/// regex matches its *entire* name, and each listed replacement yields one
+/// logical archive name to record it under.
+pub struct Channels {
+    rules: Vec<Rule>,
+}
+
+impl Channels {
+    /// Parse a comma-separated list of `regex:replacement1 replacement2` rules.
+    pub fn parse(spec: &str) -> Result<Channels, Error> {
+        let mut rules = Vec::new();
+        for rule in spec.split(',') {
+            let rule = rule.trim();
+            if rule.is_empty() {
+                continue;
+            }
+            let idx = rule.find(':').ok_or_else(|| {
+                Error::git(&format!("Invalid channel rule {:?}: missing ':'", rule))
+            })?;
+            let regex = Regex::new(&rule[..idx]).map_err(|e| {
+                Error::git(&format!("Invalid channel regex {:?}: {}", &rule[..idx], e))
+            })?;
+            let replacements = rule[idx + 1..]
+                .split_whitespace()
+                .map(String::from)
+                .collect();
+            rules.push(Rule { regex, replacements });
+        }
+        Ok(Channels { rules })
+    }
+
+    /// The default, applied when no `refs` rules are configured: every ref
+    /// is kept under its own name, matching the old unconditional behavior.
+    pub fn match_all() -> Channels {
+        Channels {
+            rules: vec![Rule {
+                regex: Regex::new("(.*)").unwrap(),
+                replacements: vec!["$1".into()],
+            }],
+        }
+    }
+
+    /// Resolve the logical archive names `name` should be recorded under.
+    /// Empty means no rule matched and the ref should not be archived at all.
+    pub fn resolve(&self, name: &str) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        for rule in &self.rules {
+            let full_match = rule.regex
+                .find(name)
+                .map_or(false, |m| m.start() == 0 && m.end() == name.len());
+            if !full_match {
+                continue;
+            }
+            for replacement in &rule.replacements {
+                names.insert(rule.regex.replace(name, replacement.as_str()).into_owned());
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Channels;
+    use std::collections::BTreeSet;
+
+    fn set(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_match_all_keeps_every_ref_under_its_own_name() {
+        let channels = Channels::match_all();
+        assert_eq!(channels.resolve("master"), set(&["master"]));
+        assert_eq!(channels.resolve("release/1.0"), set(&["release/1.0"]));
+    }
+
+    #[test]
+    fn test_unmatched_ref_resolves_to_empty() {
+        let channels = Channels::parse("^feature/.*$: $0").unwrap();
+        assert!(channels.resolve("master").is_empty());
+    }
+
+    #[test]
+    fn test_match_must_cover_the_entire_name() {
+        let channels = Channels::parse("feature: $0").unwrap();
+        // A match that only covers a prefix of the name doesn't count
+        assert!(channels.resolve("feature/foo").is_empty());
+        // Nor does one that starts partway through the name
+        assert!(channels.resolve("xfeature").is_empty());
+        // Only an exact, full-length match is kept
+        assert_eq!(channels.resolve("feature"), set(&["feature"]));
+    }
+
+    #[test]
+    fn test_single_replacement() {
+        let channels = Channels::parse(r"release/(.*): stable-$1").unwrap();
+        assert_eq!(channels.resolve("release/1.0"), set(&["stable-1.0"]));
+    }
+
+    #[test]
+    fn test_multiple_replacements_fan_out() {
+        let channels = Channels::parse(r"release/(.*): stable-$1 mirror-$1").unwrap();
+        assert_eq!(
+            channels.resolve("release/1.0"),
+            set(&["stable-1.0", "mirror-1.0"]),
+        );
+    }
+
+    #[test]
+    fn test_duplicate_replacements_are_deduplicated() {
+        let channels = Channels::parse(r"release/(.*): stable-$1 stable-$1").unwrap();
+        assert_eq!(channels.resolve("release/1.0"), set(&["stable-1.0"]));
+    }
+
+    #[test]
+    fn test_multiple_rules_are_comma_separated() {
+        let channels = Channels::parse(r"^master$: $0, ^release/(.*)$: stable-$1").unwrap();
+        assert_eq!(channels.resolve("master"), set(&["master"]));
+        assert_eq!(channels.resolve("release/2.0"), set(&["stable-2.0"]));
+        assert!(channels.resolve("feature/x").is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_rule_without_colon() {
+        assert!(Channels::parse("not-a-valid-rule").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_regex() {
+        assert!(Channels::parse("(: $0").is_err());
+    }
+}
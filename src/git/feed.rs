@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::path::Path;
+
+use crate::Error;
+
+/// One entry rendered into the feed: a ref transition at a point in time.
+struct FeedItem {
+    title: String,
+    description: String,
+    guid: String,
+    pub_date: String,
+}
+
+/// Emit an RSS feed describing every ref transition recorded in the `refs`
+/// table since `since` (or the whole history if `None`): a branch/tag being
+/// created, moved to a new SHA, or deleted.
+///
+/// Mirrors label-tracker's split between a sync step (`update_with_date`,
+/// which writes the history) and this separate emit step, which only reads
+/// it back.
+pub fn emit_refs_feed(
+    repository: &Path,
+    since: Option<DateTime<Utc>>,
+) -> Result<String, Error> {
+    let db = Connection::open(repository.join("gitarchive.sqlite3"))?;
+
+    let since = since
+        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "0000-01-01 00:00:00".into());
+
+    // Load the full history (not just the window) so that a closed row can
+    // be told apart from a true deletion: if the next row for the same name
+    // picks up exactly where this one left off, it was a move, not a delete.
+    let mut stmt = db.prepare(
+        "SELECT name, sha, tag, from_date, to_date FROM refs ORDER BY name, from_date ASC;",
+    )?;
+    let rows: Vec<(String, String, bool, String, Option<String>)> = stmt
+        .query_map(rusqlite::NO_PARAMS, |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut items = Vec::new();
+    for (i, (name, sha, tag, from_date, to_date)) in rows.iter().enumerate() {
+        let kind = if *tag { "tag" } else { "branch" };
+        // A row only continues the previous one for the same name (a
+        // "move") if the two spans are contiguous, i.e. this row picks up
+        // exactly where the last one's to_date left off - the same test
+        // `superseded` below uses in the other direction. A gap between
+        // them means the ref was deleted and is now being re-created, which
+        // should be reported as "created", not "moved".
+        let is_first = i == 0
+            || rows[i - 1].0 != *name
+            || rows[i - 1].4.as_deref() != Some(from_date.as_str());
+
+        if *from_date >= since {
+            items.push(FeedItem {
+                title: format!("{} {} {}", kind, name, if is_first { "created" } else { "moved" }),
+                description: format!("{} {:?} is now at {}", kind, name, sha),
+                guid: format!("{}@{}", name, from_date),
+                pub_date: from_date.clone(),
+            });
+        }
+
+        if let Some(to_date) = to_date {
+            let superseded = rows.get(i + 1)
+                .map_or(false, |next| next.0 == *name && &next.3 == to_date);
+            if !superseded && to_date >= &since {
+                items.push(FeedItem {
+                    title: format!("{} {} deleted", kind, name),
+                    description: format!("{} {:?} (was at {}) was removed", kind, name, sha),
+                    guid: format!("{}@{}", name, to_date),
+                    pub_date: to_date.clone(),
+                });
+            }
+        }
+    }
+    items.sort_by(|a, b| a.pub_date.cmp(&b.pub_date));
+
+    render_rss("doublegit ref history", &items)
+}
+
+/// Parse a `from_date`/`to_date` string, stored as `%Y-%m-%d %H:%M:%S` UTC,
+/// back into the RFC-822 format (e.g. `Thu, 01 Jan 2026 00:00:00 +0000`)
+/// RSS 2.0 requires for `<pubDate>`.
+fn rfc2822(date: &str) -> Result<String, Error> {
+    let naive = chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| Error::git(&format!("Invalid stored date {:?}: {}", date, e)))?;
+    Ok(DateTime::<Utc>::from_utc(naive, Utc).to_rfc2822())
+}
+
+fn render_rss(title: &str, items: &[FeedItem]) -> Result<String, Error> {
+    let mut body = String::new();
+    for item in items {
+        body.push_str(&format!(
+            "    <item>\n      <title>{}</title>\n      <description>{}</description>\n      <guid isPermaLink=\"false\">{}</guid>\n      <pubDate>{}</pubDate>\n    </item>\n",
+            escape_xml(&item.title),
+            escape_xml(&item.description),
+            escape_xml(&item.guid),
+            escape_xml(&rfc2822(&item.pub_date)?),
+        ));
+    }
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n  <channel>\n    <title>{}</title>\n    <link>{}</link>\n    <description>{}</description>\n{}  </channel>\n</rss>\n",
+        escape_xml(title),
+        escape_xml(FEED_LINK),
+        escape_xml(FEED_DESCRIPTION),
+        body,
+    ))
+}
+
+/// Placeholder channel-level `<link>`/`<description>`: doublegit has no
+/// notion of a public URL for the archive itself, but RSS 2.0 requires both
+/// to be present for the feed to validate.
+const FEED_LINK: &str = "https://github.com/SilentByte/doublegit";
+const FEED_DESCRIPTION: &str = "Ref history archived by doublegit";
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
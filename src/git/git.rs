@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use super::Ref;
+use crate::Error;
+
+/// Refs added, changed, or removed by a single `fetch`.
+pub struct FetchOutput {
+    pub new: Vec<Ref>,
+    pub changed: Vec<Ref>,
+    pub removed: Vec<Ref>,
+}
+
+fn run(repository: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()
+        .map_err(|e| Error::git(&format!("Failed to spawn git: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::git(&format!(
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr),
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// List every remote-tracking branch and tag, mapped to its current SHA.
+fn list_known_refs(repository: &Path) -> Result<HashMap<Ref, String>, Error> {
+    let out = run(
+        repository,
+        &[
+            "for-each-ref",
+            "--format=%(refname) %(objectname)",
+            "refs/remotes/origin",
+            "refs/tags",
+        ],
+    )?;
+
+    let mut refs = HashMap::new();
+    for line in out.lines() {
+        let mut parts = line.splitn(2, ' ');
+        let (name, sha) = match (parts.next(), parts.next()) {
+            (Some(n), Some(s)) => (n, s),
+            _ => continue,
+        };
+        let ref_ = if let Some(tag) = name.strip_prefix("refs/tags/") {
+            Ref { name: tag.into(), tag: true }
+        } else if let Some(branch) = name.strip_prefix("refs/remotes/") {
+            match Ref::parse_remote_ref(branch) {
+                Ok(r) => r,
+                Err(_) => continue,
+            }
+        } else {
+            continue;
+        };
+        refs.insert(ref_, sha.to_string());
+    }
+    Ok(refs)
+}
+
+/// Fetch from `origin`, pruning stale remote-tracking branches, and report
+/// which refs were added, changed, or removed compared to before the fetch.
+pub fn fetch(repository: &Path) -> Result<FetchOutput, Error> {
+    let before = list_known_refs(repository)?;
+    run(repository, &["fetch", "--prune", "--tags", "origin"])?;
+    let after = list_known_refs(repository)?;
+
+    let mut new = Vec::new();
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+
+    for (ref_, sha) in &after {
+        match before.get(ref_) {
+            None => new.push(ref_.clone()),
+            Some(old_sha) if old_sha != sha => changed.push(ref_.clone()),
+            Some(_) => {}
+        }
+    }
+    for ref_ in before.keys() {
+        if !after.contains_key(ref_) {
+            removed.push(ref_.clone());
+        }
+    }
+
+    Ok(FetchOutput { new, changed, removed })
+}
+
+/// Resolve a ref or commit-ish to its SHA.
+pub fn get_sha(repository: &Path, refname: &str) -> Result<String, Error> {
+    Ok(run(repository, &["rev-parse", refname])?.trim().to_string())
+}
+
+/// Whether `sha` names an annotated tag object, as opposed to a commit.
+pub fn is_annotated_tag(repository: &Path, sha: &str) -> Result<bool, Error> {
+    Ok(run(repository, &["cat-file", "-t", sha])?.trim() == "tag")
+}
+
+/// Create or move a ref to point at `sha`.
+pub fn make_ref(repository: &Path, refname: &str, sha: &str) -> Result<(), Error> {
+    run(repository, &["update-ref", refname, sha])?;
+    Ok(())
+}
+
+/// Create or move a local branch to point at `sha`.
+pub fn make_branch(repository: &Path, name: &str, sha: &str) -> Result<(), Error> {
+    run(repository, &["branch", "-f", name, sha])?;
+    Ok(())
+}
+
+/// Force-delete a local branch.
+pub fn delete_branch(repository: &Path, name: &str) -> Result<(), Error> {
+    run(repository, &["branch", "-D", name])?;
+    Ok(())
+}
+
+/// Local branches that are fully contained within `sha`'s history (i.e.
+/// would become superfluous once a branch at `sha` exists).
+pub fn included_branches(repository: &Path, sha: &str) -> Result<Vec<String>, Error> {
+    list_branches(repository, &["branch", "--format=%(refname:short)", "--merged", sha])
+}
+
+/// Local branches whose history contains `sha`.
+pub fn including_branches(repository: &Path, sha: &str) -> Result<Vec<String>, Error> {
+    list_branches(repository, &["branch", "--format=%(refname:short)", "--contains", sha])
+}
+
+fn list_branches(repository: &Path, args: &[&str]) -> Result<Vec<String>, Error> {
+    Ok(run(repository, args)?
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Whether `ancestor` is an ancestor of (or equal to) `descendant`.
+pub fn is_ancestor(repository: &Path, ancestor: &str, descendant: &str) -> Result<bool, Error> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(&["merge-base", "--is-ancestor", ancestor, descendant])
+        .status()
+        .map_err(|e| Error::git(&format!("Failed to spawn git: {}", e)))?;
+    match status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => Err(Error::git("git merge-base --is-ancestor failed unexpectedly")),
+    }
+}
+
+/// Write a bundle capturing everything reachable from `tips` to `out_path`,
+/// creating parent directories as needed.
+pub fn create_bundle(repository: &Path, out_path: &Path, tips: &[&str]) -> Result<(), Error> {
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // `run` passes `-C repository` to git, so the child's cwd becomes
+    // `repository`, not ours. A relative `out_path` (even one already
+    // prefixed with `repository`, as callers construct it) would then get
+    // resolved a second time against that cwd, doubling it. Make it
+    // absolute first so it means the same thing to git as it does to us.
+    let out_path = if out_path.is_absolute() {
+        out_path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(out_path)
+    };
+    let out_path = out_path.to_string_lossy().into_owned();
+    let mut args = vec!["bundle", "create", &out_path];
+    args.extend_from_slice(tips);
+    run(repository, &args)?;
+    Ok(())
+}
+
+/// The dedicated namespace doublegit's own notes live in, kept separate from
+/// any notes the user or other tools may already be attaching.
+const NOTES_REF: &str = "refs/notes/doublegit";
+
+/// Append `content` as a note on `sha`, in the `refs/notes/doublegit`
+/// namespace. Notes live in their own versioned ref, so this metadata
+/// survives even after `keep-*` branches are pruned.
+pub fn append_note(repository: &Path, sha: &str, content: &str) -> Result<(), Error> {
+    let note_ref = format!("--ref={}", NOTES_REF);
+    run(
+        repository,
+        &["notes", note_ref.as_str(), "append", "-m", content, sha],
+    )?;
+    Ok(())
+}
+
+/// Read back the note attached to `sha`, if any.
+pub fn read_note(repository: &Path, sha: &str) -> Result<Option<String>, Error> {
+    let note_ref = format!("--ref={}", NOTES_REF);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(&["notes", note_ref.as_str(), "show", sha])
+        .output()
+        .map_err(|e| Error::git(&format!("Failed to spawn git: {}", e)))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// List every commit that has a note in the `refs/notes/doublegit` namespace.
+pub fn list_noted_commits(repository: &Path) -> Result<Vec<String>, Error> {
+    let note_ref = format!("--ref={}", NOTES_REF);
+    let out = run(repository, &["notes", note_ref.as_str(), "list"])?;
+    Ok(out
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(String::from)
+        .collect())
+}
@@ -1,9 +1,18 @@
+mod channel;
+mod feed;
 mod git;
+mod notes;
 
 #[cfg(test)] mod tests_integration;
 
+pub use feed::emit_refs_feed;
+pub use notes::{read_observations, RefObservation};
+
+use channel::Channels;
+
 use rusqlite::Connection;
 use rusqlite::types::ToSql;
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::path::Path;
 
@@ -53,7 +62,7 @@ pub fn update_with_date(
         let db = Connection::open(db_path)?;
         if !exists {
             warn!("Database doesn't exist, creating tables...");
-            db.execute(
+            db.execute_batch(
                 "
                 CREATE TABLE refs(
                     name TEXT NOT NULL,
@@ -63,9 +72,21 @@ pub fn update_with_date(
                     tag BOOLEAN NOT NULL
                 );
                 ",
-                rusqlite::NO_PARAMS,
             )?;
         }
+        // Unconditional, so upgrading a repository archived before bundle
+        // support was added still gets the table, instead of only new DBs
+        db.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS bundles(
+                sha TEXT NOT NULL,
+                path TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                tips TEXT NOT NULL,
+                created DATETIME NOT NULL
+            );
+            ",
+        )?;
         db
     };
     let tx = db.transaction()?;
@@ -73,34 +94,54 @@ pub fn update_with_date(
     // Do fetch
     let out = git::fetch(repository)?;
 
+    // Load the configured ref routing rules, defaulting to archiving every
+    // ref under its own name (the old unconditional behavior), and whether
+    // observations should also be annotated onto commits as git notes
+    let (channels, annotate) = load_config(repository)?;
+
     // Convert time to string
     let date = date.format("%Y-%m-%d %H:%M:%S").to_string();
 
     // Update database
     for ref_ in out.removed.iter().chain(out.changed.iter()) {
-        tx.execute(
-            "
-            UPDATE refs SET to_date=?
-            WHERE name=?
-            ORDER BY from_date DESC
-            LIMIT 1;
-            ",
-            &[&date, &ref_.name],
-        )?;
+        for channel in channels.resolve(&ref_.name) {
+            tx.execute(
+                "
+                UPDATE refs SET to_date=?
+                WHERE name=?
+                ORDER BY from_date DESC
+                LIMIT 1;
+                ",
+                &[&date, &channel],
+            )?;
+        }
     }
     for ref_ in out.changed.iter().chain(out.new.iter()) {
+        let ref_channels = channels.resolve(&ref_.name);
+        if ref_channels.is_empty() {
+            continue;
+        }
         let sha = git::get_sha(repository, &ref_.fullname())?;
-        tx.execute(
-            "
-            INSERT INTO refs(name, from_date, to_date, sha, tag)
-            VALUES(?, ?, NULL, ?, ?);
-            ",
-            &[&ref_.name, &date, &sha, &ref_.tag as &dyn ToSql],
-        )?;
+        let is_new = out.new.contains(ref_);
+        for channel in &ref_channels {
+            tx.execute(
+                "
+                INSERT INTO refs(name, from_date, to_date, sha, tag)
+                VALUES(?, ?, NULL, ?, ?);
+                ",
+                &[channel, &date, &sha, &ref_.tag as &dyn ToSql],
+            )?;
+            if annotate {
+                notes::record_observation(repository, &sha, channel, "origin", &date, is_new)?;
+            }
+        }
     }
 
     // Create refs to prevent garbage collection
     for ref_ in out.changed.iter().chain(out.new.iter()) {
+        if channels.resolve(&ref_.name).is_empty() {
+            continue;
+        }
         let sha = git::get_sha(repository, &ref_.fullname())?;
         if ref_.tag && git::is_annotated_tag(repository, &sha)? {
             info!("{:?} making ref {}", ref_, sha);
@@ -117,6 +158,9 @@ pub fn update_with_date(
 
     // Remove superfluous branches
     for ref_ in out.changed.iter().chain(out.new.iter()) {
+        if channels.resolve(&ref_.name).is_empty() {
+            continue;
+        }
         let sha = git::get_sha(repository, &ref_.fullname())?;
         let keeper = format!("keep-{}", sha);
         // Parents of this branch are superfluous
@@ -135,11 +179,93 @@ pub fn update_with_date(
         }
     }
 
+    // Export point-in-time snapshots as git bundles. This is a portable,
+    // verifiable complement to the keep-branches above: a bundle can be
+    // copied off-host and `unbundle`d into a fresh repository independently
+    // of the original remote, and its SHA-256 hash lets a copy be verified.
+    let bundles_dir = repository.join("bundles");
+    for ref_ in out.changed.iter().chain(out.new.iter()) {
+        if channels.resolve(&ref_.name).is_empty() {
+            continue;
+        }
+        let sha = git::get_sha(repository, &ref_.fullname())?;
+        let bundle_path = bundles_dir.join(format!("{}.bundle", sha));
+
+        if !bundle_path.exists() {
+            // git refuses to bundle a bare SHA ("empty bundle"), so name the
+            // tip by its full ref, which currently points at `sha`
+            let tip = if ref_.tag {
+                format!("refs/tags/{}", ref_.name)
+            } else {
+                format!("refs/remotes/origin/{}", ref_.name)
+            };
+            git::create_bundle(repository, &bundle_path, &[&tip])?;
+            let hash = hash_file(&bundle_path)?;
+            tx.execute(
+                "
+                INSERT INTO bundles(sha, path, hash, tips, created)
+                VALUES(?, ?, ?, ?, ?);
+                ",
+                &[
+                    &sha,
+                    &bundle_path.to_string_lossy().into_owned(),
+                    &hash,
+                    &sha,
+                    &date,
+                ],
+            )?;
+        }
+
+        // Prune bundles whose objects are now subsumed by this one
+        let superseded: Vec<(String, String)> = {
+            let mut stmt = tx.prepare("SELECT sha, path FROM bundles WHERE sha != ?;")?;
+            stmt.query_map(&[&sha], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()?
+        };
+        for (old_sha, old_path) in superseded {
+            if git::is_ancestor(repository, &old_sha, &sha)? {
+                info!("Bundle for {} is subsumed by {}, pruning", old_sha, sha);
+                let _ = std::fs::remove_file(&old_path);
+                tx.execute("DELETE FROM bundles WHERE sha=?;", &[&old_sha])?;
+            }
+        }
+    }
+
     tx.commit()?;
 
     Ok(())
 }
 
+/// Load the ref routing rules from `doublegit.json`'s `refs` field (falling
+/// back to archiving every ref under its own name), and whether its `notes`
+/// field opts into annotating archived commits with git notes.
+fn load_config(repository: &Path) -> Result<(Channels, bool), Error> {
+    let config_file = repository.join("doublegit.json");
+    if !config_file.exists() {
+        return Ok((Channels::match_all(), false));
+    }
+
+    let file = std::fs::File::open(&config_file)?;
+    let config: serde_json::Value = serde_json::from_reader(file)
+        .map_err(|e| Error::git(&format!("Error reading config: {}", e)))?;
+
+    let channels = match config.get("refs").and_then(|v| v.as_str()) {
+        Some(spec) => Channels::parse(spec)?,
+        None => Channels::match_all(),
+    };
+    let annotate = config.get("notes").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    Ok((channels, annotate))
+}
+
+/// Compute the hex-encoded SHA-256 digest of a file's contents.
+fn hash_file(path: &Path) -> Result<String, Error> {
+    let bytes = std::fs::read(path)?;
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::Ref;
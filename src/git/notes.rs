@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use super::git;
+use crate::Error;
+
+/// Metadata describing one archival observation of a ref, as attached via a
+/// git note in the `refs/notes/doublegit` namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefObservation {
+    pub name: String,
+    pub remote: String,
+    pub date: String,
+    pub new: bool,
+}
+
+impl RefObservation {
+    fn render(&self) -> String {
+        format!(
+            "name={}\nremote={}\ndate={}\nnew={}\n",
+            self.name, self.remote, self.date, self.new,
+        )
+    }
+
+    fn parse(block: &str) -> Option<RefObservation> {
+        let mut name = None;
+        let mut remote = None;
+        let mut date = None;
+        let mut new = None;
+        for line in block.lines() {
+            let (key, value) = line.split_once('=')?;
+            match key {
+                "name" => name = Some(value.to_string()),
+                "remote" => remote = Some(value.to_string()),
+                "date" => date = Some(value.to_string()),
+                "new" => new = Some(value == "true"),
+                _ => {}
+            }
+        }
+        Some(RefObservation {
+            name: name?,
+            remote: remote?,
+            date: date?,
+            new: new?,
+        })
+    }
+}
+
+/// Attach a note to `sha` recording that `name` (fetched from `remote`) was
+/// observed there on `date`, either newly created or changed.
+pub fn record_observation(
+    repository: &Path,
+    sha: &str,
+    name: &str,
+    remote: &str,
+    date: &str,
+    new: bool,
+) -> Result<(), Error> {
+    let observation = RefObservation {
+        name: name.into(),
+        remote: remote.into(),
+        date: date.into(),
+        new,
+    };
+    git::append_note(repository, sha, &observation.render())
+}
+
+/// Read back every observation left by `record_observation`, as
+/// `(sha, observation)` pairs, purely from the git notes. Even if
+/// `gitarchive.sqlite3` is lost, the timeline of when each ref was created
+/// or moved can be rebuilt from the repository's objects alone (e.g. via
+/// `git log --notes=doublegit`). Removals write no note, so the `to_date`
+/// of a deleted ref in the `refs` table cannot be recovered from them.
+pub fn read_observations(repository: &Path) -> Result<Vec<(String, RefObservation)>, Error> {
+    let mut observations = Vec::new();
+    for sha in git::list_noted_commits(repository)? {
+        let content = match git::read_note(repository, &sha)? {
+            Some(content) => content,
+            None => continue,
+        };
+        for block in content.split("\n\n") {
+            if let Some(observation) = RefObservation::parse(block) {
+                observations.push((sha.clone(), observation));
+            }
+        }
+    }
+    Ok(observations)
+}
@@ -0,0 +1,92 @@
+//! Integration tests that exercise `update_with_date` end-to-end against a
+//! real git repository, unlike the unit tests in `mod.rs`, which only check
+//! ref-name parsing in isolation.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use super::update_with_date;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .status()
+        .expect("failed to spawn git");
+    assert!(status.success(), "git {:?} failed in {:?}", args, dir);
+}
+
+/// A scratch directory under the system temp dir, removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(label: &str) -> TempDir {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "doublegit-test-{}-{}-{}",
+            std::process::id(),
+            label,
+            n,
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// Reproduces the bug from review: `create_bundle` used to hand git an
+/// output path that was already prefixed with `repository`, which `run`
+/// then resolved a *second* time against `repository` via `-C`, doubling it
+/// whenever `repository` was relative - exactly what `doublegit update .`
+/// does from the CLI. Calling `update_with_date` with a relative path is
+/// the only way to catch this; no other test in this crate opens a real
+/// repository, so none of them would fail with the bug present.
+#[test]
+fn test_update_with_relative_path() {
+    let origin = TempDir::new("origin");
+    git(origin.as_ref(), &["init", "--quiet"]);
+    git(origin.as_ref(), &["config", "user.email", "test@example.com"]);
+    git(origin.as_ref(), &["config", "user.name", "Test"]);
+    std::fs::write(origin.as_ref().join("file.txt"), "hello").unwrap();
+    git(origin.as_ref(), &["add", "."]);
+    git(origin.as_ref(), &["commit", "--quiet", "-m", "initial"]);
+
+    let workdir = TempDir::new("work");
+    git(
+        workdir.as_ref(),
+        &["clone", "--quiet", origin.as_ref().to_str().unwrap(), "repo"],
+    );
+    let repo_dir = workdir.as_ref().join("repo");
+
+    // Clone already leaves refs/remotes/origin/* in place, so a fetch right
+    // after it wouldn't see anything as new or changed. Drop the
+    // remote-tracking ref so the upcoming fetch reports it as new, the same
+    // as it would for a repository `doublegit` is archiving for the first
+    // time.
+    git(&repo_dir, &["update-ref", "-d", "refs/remotes/origin/master"]);
+
+    let original_cwd = std::env::current_dir().unwrap();
+    std::env::set_current_dir(workdir.as_ref()).unwrap();
+    let result = update_with_date(Path::new("repo"), chrono::Utc::now());
+    std::env::set_current_dir(original_cwd).unwrap();
+
+    result.expect("update_with_date should succeed against a relatively-pathed repository");
+
+    let bundle_count = std::fs::read_dir(repo_dir.join("bundles"))
+        .expect("bundles directory should have been created")
+        .count();
+    assert!(bundle_count > 0, "expected at least one bundle to be written");
+}